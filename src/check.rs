@@ -1,62 +1,249 @@
 use quote::quote;
 use std::cmp::Ordering;
-use syn::{Arm, Attribute, Ident, ImplItem, Result, Variant};
+use std::iter::FromIterator;
+use syn::{Arm, Attribute, Expr, Fields, Ident, ImplItem, ItemFn, Local, Result, Stmt, Variant};
 use syn::{Error, Field, Pat, PatIdent};
 
-use crate::compare::{cmp, Path, UnderscoreOrder};
+use crate::compare::{cmp, kind_of, Key, OrderMode, UnderscoreOrder};
 use crate::format;
 use crate::parse::Input::{self, *};
+use crate::parse::SortedArgs;
 
-pub fn sorted(input: &mut Input) -> Result<()> {
-    let paths = match input {
-        Enum(item) => collect_paths(&mut item.variants)?,
-        Impl(item) => collect_paths(&mut item.items)?,
-        Struct(item) => collect_paths(&mut item.fields)?,
-        Match(expr) | Let(expr) => collect_paths(&mut expr.arms)?,
-    };
+pub fn sorted(input: &mut Input, order_mode: OrderMode, fix: bool) -> Result<()> {
+    match input {
+        Enum(item) => sorted_seq(&mut item.variants, order_mode, fix),
+        Impl(item) => sorted_seq(&mut item.items, order_mode, fix),
+        Struct(item) => sorted_fields(&mut item.fields, order_mode, fix),
+        Match(expr) | Let(expr) => sorted_seq(&mut expr.arms, order_mode, fix),
+    }
+}
+
+fn sorted_fields(fields: &mut Fields, order_mode: OrderMode, fix: bool) -> Result<()> {
+    match fields {
+        Fields::Named(fields) => sorted_seq(&mut fields.named, order_mode, fix),
+        Fields::Unnamed(fields) => sorted_seq(&mut fields.unnamed, order_mode, fix),
+        Fields::Unit => Ok(()),
+    }
+}
+
+/// Checks (or, with `fix`, rewrites) a single `variants`/`fields`/`items`/`arms`
+/// collection. `C` is whatever container syn uses to hold `P` (a `Vec` or a
+/// `Punctuated`); both support owned and by-`&mut` iteration, which is all this
+/// needs.
+fn sorted_seq<C, P>(container: &mut C, order_mode: OrderMode, fix: bool) -> Result<()>
+where
+    C: Default + FromIterator<P> + IntoIterator<Item = P>,
+    for<'a> &'a mut C: IntoIterator<Item = &'a mut P>,
+    P: Sortable,
+{
+    let entries = classify(&mut *container, order_mode)?;
+    ensure_consistent_kinds(entries.iter().flatten().map(|(_, key)| key))?;
+
+    if !fix {
+        let keys: Vec<(Category, Key)> = entries.into_iter().flatten().collect();
+        return check_order(&keys, order_mode);
+    }
+
+    let items: Vec<P> = std::mem::take(container).into_iter().collect();
+    *container = reorder(items, &entries, order_mode).into_iter().collect();
+    Ok(())
+}
+
+/// Stably sorts the non-pinned (not `#[remain::unsorted]`) items of `items`
+/// into the slots left by pinned items, which keep their original position.
+fn reorder<P>(items: Vec<P>, entries: &[Option<(Category, Key)>], order_mode: OrderMode) -> Vec<P> {
+    let mut slots: Vec<Option<P>> = Vec::with_capacity(items.len());
+    let mut movable: Vec<(&(Category, Key), P)> = Vec::new();
 
-    let mode = UnderscoreOrder::First;
-    if find_misordered(&paths, mode).is_none() {
+    for (item, entry) in items.into_iter().zip(entries) {
+        match entry {
+            Some(key) => {
+                movable.push((key, item));
+                slots.push(None);
+            }
+            None => slots.push(Some(item)),
+        }
+    }
+
+    movable.sort_by(|(a, _), (b, _)| cmp(a, b, UnderscoreOrder::First, order_mode));
+
+    let mut movable = movable.into_iter().map(|(_, item)| item);
+    slots
+        .into_iter()
+        .map(|slot| slot.unwrap_or_else(|| movable.next().expect("movable item present")))
+        .collect()
+}
+
+/// Reports the first misordering in `keys`, same as the old error-only
+/// `sorted`: `keys` is accepted if it is sorted under either
+/// `UnderscoreOrder::First` or `Last`; otherwise the error is reported
+/// against the `Last` ordering.
+fn check_order(keys: &[(Category, Key)], order_mode: OrderMode) -> Result<()> {
+    let underscore_order = UnderscoreOrder::First;
+    if find_misordered(keys, underscore_order, order_mode).is_none() {
         return Ok(());
     }
 
-    let mode = UnderscoreOrder::Last;
-    let wrong = match find_misordered(&paths, mode) {
+    let underscore_order = UnderscoreOrder::Last;
+    let wrong = match find_misordered(keys, underscore_order, order_mode) {
         Some(wrong) => wrong,
         None => return Ok(()),
     };
 
-    let lesser = &paths[wrong];
-    let correct_pos = match paths[..wrong - 1].binary_search_by(|probe| cmp(probe, lesser, mode)) {
+    let lesser = &keys[wrong];
+    let correct_pos = match keys[..wrong - 1]
+        .binary_search_by(|probe| cmp(probe, lesser, underscore_order, order_mode))
+    {
         Err(correct_pos) => correct_pos,
         Ok(equal_to) => equal_to + 1,
     };
-    let greater = &paths[correct_pos];
+    let greater = &keys[correct_pos];
     Err(format::error(&lesser.1, &greater.1))
 }
 
-fn find_misordered(paths: &[(Category, Path)], mode: UnderscoreOrder) -> Option<usize> {
-    for i in 1..paths.len() {
-        if cmp(&paths[i], &paths[i - 1], mode) == Ordering::Less {
-            return Some(i);
+/// Entry point for `#[remain::check]`: scan a function body for statements
+/// carrying a bare `#[sorted]` attribute and check (or fix) them the same way
+/// an item-level `#[remain::sorted]` would be. `default` is `#[remain::check]`'s
+/// own args, used whenever a nested `#[sorted]` doesn't specify its own.
+pub fn check_fn(item_fn: &mut ItemFn, default: SortedArgs) -> Result<()> {
+    for stmt in &mut item_fn.block.stmts {
+        check_stmt(stmt, default)?;
+    }
+
+    Ok(())
+}
+
+fn check_stmt(stmt: &mut Stmt, default: SortedArgs) -> Result<()> {
+    match stmt {
+        Stmt::Local(local) => check_local(local, default),
+        Stmt::Expr(Expr::Match(expr)) | Stmt::Semi(Expr::Match(expr), _) => {
+            if let Some(args) = remove_sorted_attr(&mut expr.attrs, default)? {
+                let mut input = Input::Match(expr.clone());
+                sorted(&mut input, args.mode, args.fix)?;
+                if let Input::Match(checked) = input {
+                    *expr = checked;
+                }
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_local(local: &mut Local, default: SortedArgs) -> Result<()> {
+    let args = match remove_sorted_attr(&mut local.attrs, default)? {
+        Some(args) => args,
+        None => return Ok(()),
+    };
+
+    match &mut local.init {
+        Some((_, init)) => match &mut **init {
+            Expr::Match(expr) => {
+                let mut input = Input::Let(expr.clone());
+                sorted(&mut input, args.mode, args.fix)?;
+                if let Input::Let(checked) = input {
+                    *expr = checked;
+                }
+                Ok(())
+            }
+            other => Err(Error::new_spanned(
+                other,
+                "unsupported by #[remain::sorted]",
+            )),
+        },
+        None => Err(Error::new_spanned(
+            local,
+            "unsupported by #[remain::sorted]",
+        )),
+    }
+}
+
+/// A bare nested `#[sorted]` (no args) inherits `default`, the enclosing
+/// `#[remain::check]`'s own args; `#[sorted(...)]` with its own args always
+/// overrides `default` wholesale.
+fn remove_sorted_attr(
+    attrs: &mut Vec<Attribute>,
+    default: SortedArgs,
+) -> Result<Option<SortedArgs>> {
+    for i in 0..attrs.len() {
+        let path = &attrs[i].path;
+        let path = quote!(#path).to_string();
+        if path == "sorted" || path == "remain :: sorted" {
+            let attr = attrs.remove(i);
+            if attr.tokens.is_empty() {
+                return Ok(Some(default));
+            }
+            return attr.parse_args::<SortedArgs>().map(Some);
+        }
+    }
+
+    Ok(None)
+}
+
+fn find_misordered(
+    keys: &[(Category, Key)],
+    underscore_order: UnderscoreOrder,
+    order_mode: OrderMode,
+) -> Option<usize> {
+    (1..keys.len())
+        .find(|&i| cmp(&keys[i], &keys[i - 1], underscore_order, order_mode) == Ordering::Less)
+}
+
+/// A match over literal or range arms can only be ordered sensibly when
+/// every arm's key belongs to the same family; a wildcard has no family of
+/// its own and is exempt from the check.
+fn ensure_consistent_kinds<'a>(keys: impl IntoIterator<Item = &'a Key>) -> Result<()> {
+    let mut expected = None;
+
+    for key in keys {
+        let kind = match kind_of(key) {
+            Some(kind) => kind,
+            None => continue,
+        };
+
+        match expected {
+            None => expected = Some(kind),
+            Some(expected) if expected == kind => {}
+            Some(_) => {
+                let msg = "#[remain::sorted] cannot mix different kinds of literal patterns";
+                return Err(Error::new(key.span(), msg));
+            }
         }
     }
 
-    None
+    Ok(())
 }
 
-fn collect_paths<'a, I, P>(iter: I) -> Result<Vec<(Category, Path)>>
+/// Strips and records each item's sort key, leaving `None` for items pinned
+/// via `#[remain::unsorted]` so their position in the collection can be
+/// preserved later.
+fn classify<'a, I, P>(iter: I, order_mode: OrderMode) -> Result<Vec<Option<(Category, Key)>>>
 where
     I: IntoIterator<Item = &'a mut P>,
     P: Sortable + 'a,
 {
     iter.into_iter()
-        .filter_map(|item| {
-            if remove_unsorted_attr(item.attrs()) {
-                None
-            } else {
-                Some(item.to_path().map(|path| (item.category(), path)))
+        .map(|item| {
+            // Both marker attributes must be stripped unconditionally, even
+            // for a pinned item, so `#[remain::key(...)]` never leaks into
+            // the emitted tokens. A pinned item also skips `validate`
+            // entirely: it's exempt from ordering checks, inner or-pattern
+            // ordering included.
+            let unsorted = remove_unsorted_attr(item.attrs());
+            let key_override = remove_key_attr(item.attrs())?;
+
+            if unsorted {
+                return Ok(None);
             }
+
+            item.validate(order_mode)?;
+
+            let key = match key_override {
+                Some((value, span)) => Key::Custom(value, span),
+                None => item.to_key()?,
+            };
+
+            Ok(Some((item.category(), key)))
         })
         .collect()
 }
@@ -74,20 +261,41 @@ fn remove_unsorted_attr(attrs: &mut Vec<Attribute>) -> bool {
     false
 }
 
+/// `#[remain::key("...")]` overrides the string an item sorts by without
+/// renaming the item itself.
+fn remove_key_attr(attrs: &mut Vec<Attribute>) -> Result<Option<(String, proc_macro2::Span)>> {
+    for i in 0..attrs.len() {
+        let path = &attrs[i].path;
+        let path = quote!(#path).to_string();
+        if path == "key" || path == "remain :: key" {
+            let attr = attrs.remove(i);
+            let lit: syn::LitStr = attr.parse_args()?;
+            return Ok(Some((lit.value(), lit.span())));
+        }
+    }
+
+    Ok(None)
+}
+
 trait Sortable {
-    fn to_path(&self) -> Result<Path>;
+    fn to_key(&self) -> Result<Key>;
     fn attrs(&mut self) -> &mut Vec<Attribute>;
 
     fn category(&self) -> u8 {
         0
     }
+
+    /// Extra validation beyond the item's own sort key. `Arm` uses this to
+    /// check that an or-pattern's cases are themselves sorted relative to
+    /// one another, not just the first case against neighboring arms.
+    fn validate(&self, _order_mode: OrderMode) -> Result<()> {
+        Ok(())
+    }
 }
 
 impl Sortable for Variant {
-    fn to_path(&self) -> Result<Path> {
-        Ok(Path {
-            segments: vec![self.ident.clone()],
-        })
+    fn to_key(&self) -> Result<Key> {
+        Ok(Key::Ident(vec![self.ident.clone()]))
     }
     fn attrs(&mut self) -> &mut Vec<Attribute> {
         &mut self.attrs
@@ -95,10 +303,11 @@ impl Sortable for Variant {
 }
 
 impl Sortable for Field {
-    fn to_path(&self) -> Result<Path> {
-        Ok(Path {
-            segments: vec![self.ident.clone().expect("must be named field")],
-        })
+    fn to_key(&self) -> Result<Key> {
+        Ok(Key::Ident(vec![self
+            .ident
+            .clone()
+            .expect("must be named field")]))
     }
     fn attrs(&mut self) -> &mut Vec<Attribute> {
         &mut self.attrs
@@ -106,7 +315,7 @@ impl Sortable for Field {
 }
 
 impl Sortable for ImplItem {
-    fn to_path(&self) -> Result<Path> {
+    fn to_key(&self) -> Result<Key> {
         let segments = match self {
             Self::Const(c) => vec![c.ident.clone()],
             Self::Type(t) => vec![t.ident.clone()],
@@ -118,7 +327,7 @@ impl Sortable for ImplItem {
             }
         };
 
-        Ok(Path { segments })
+        Ok(Key::Ident(segments))
     }
 
     fn attrs(&mut self) -> &mut Vec<Attribute> {
@@ -143,30 +352,101 @@ impl Sortable for ImplItem {
 }
 
 impl Sortable for Arm {
-    fn to_path(&self) -> Result<Path> {
+    fn to_key(&self) -> Result<Key> {
         // Sort by just the first pat.
         let pat = match &self.pat {
             Pat::Or(pat) => pat.cases.iter().next().expect("at least one pat"),
             _ => &self.pat,
         };
 
-        let segments = match pat {
-            Pat::Ident(pat) if is_just_ident(pat) => vec![pat.ident.clone()],
-            Pat::Path(pat) => idents_of_path(&pat.path),
-            Pat::Struct(pat) => idents_of_path(&pat.path),
-            Pat::TupleStruct(pat) => idents_of_path(&pat.path),
-            Pat::Wild(pat) => vec![Ident::from(pat.underscore_token)],
-            other => {
-                let msg = "unsupported by #[remain::sorted]";
-                return Err(Error::new_spanned(other, msg));
-            }
-        };
-
-        Ok(Path { segments })
+        key_from_pat(pat)
     }
     fn attrs(&mut self) -> &mut Vec<Attribute> {
         &mut self.attrs
     }
+
+    fn validate(&self, order_mode: OrderMode) -> Result<()> {
+        match &self.pat {
+            Pat::Or(pat) => check_or_pattern(pat, order_mode),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// `Arm::to_key` only looks at the first case of an `A | B | C` or-pattern,
+/// so this separately checks that the cases are sorted among themselves.
+/// A case that `key_from_pat` rejects (bound, tuple, ...) is skipped here
+/// rather than turned into an error; if it is the first case, `to_key` still
+/// reports it as unsupported.
+fn check_or_pattern(pat: &syn::PatOr, order_mode: OrderMode) -> Result<()> {
+    let keys: Vec<(Category, Key)> = pat
+        .cases
+        .iter()
+        .filter_map(|case| key_from_pat(case).ok().map(|key| (0, key)))
+        .collect();
+
+    ensure_consistent_kinds(keys.iter().map(|(_, key)| key))?;
+    check_order(&keys, order_mode)
+}
+
+fn key_from_pat(pat: &Pat) -> Result<Key> {
+    match pat {
+        Pat::Ident(pat) if is_just_ident(pat) => Ok(Key::Ident(vec![pat.ident.clone()])),
+        Pat::Path(pat) => Ok(Key::Ident(idents_of_path(&pat.path))),
+        Pat::Struct(pat) => Ok(Key::Ident(idents_of_path(&pat.path))),
+        Pat::TupleStruct(pat) => Ok(Key::Ident(idents_of_path(&pat.path))),
+        Pat::Wild(pat) => Ok(Key::Wild(pat.underscore_token.span)),
+        Pat::Lit(pat) => key_from_expr(&pat.expr),
+        Pat::Range(pat) => Ok(Key::Range {
+            start: Box::new(key_from_expr(&pat.lo)?),
+            end: Box::new(key_from_expr(&pat.hi)?),
+            inclusive: matches!(pat.limits, syn::RangeLimits::Closed(_)),
+        }),
+        other => {
+            let msg = "unsupported by #[remain::sorted]";
+            Err(Error::new_spanned(other, msg))
+        }
+    }
+}
+
+fn key_from_expr(expr: &Expr) -> Result<Key> {
+    use syn::spanned::Spanned;
+
+    match expr {
+        Expr::Lit(expr) => key_from_lit(&expr.lit),
+        Expr::Unary(expr) if matches!(expr.op, syn::UnOp::Neg(_)) => {
+            match key_from_expr(&expr.expr)? {
+                Key::Int(value, _) => Ok(Key::Int(-value, expr.span())),
+                _ => {
+                    let msg = "unsupported by #[remain::sorted]";
+                    Err(Error::new_spanned(expr, msg))
+                }
+            }
+        }
+        other => {
+            let msg = "unsupported by #[remain::sorted]";
+            Err(Error::new_spanned(other, msg))
+        }
+    }
+}
+
+fn key_from_lit(lit: &syn::Lit) -> Result<Key> {
+    match lit {
+        syn::Lit::Int(lit) => {
+            let value = lit
+                .base10_parse::<i128>()
+                .map_err(|_| Error::new_spanned(lit, "unsupported by #[remain::sorted]"))?;
+            Ok(Key::Int(value, lit.span()))
+        }
+        syn::Lit::Byte(lit) => Ok(Key::Int(i128::from(lit.value()), lit.span())),
+        syn::Lit::Char(lit) => Ok(Key::Char(lit.value(), lit.span())),
+        syn::Lit::ByteStr(lit) => Ok(Key::Bytes(lit.value(), lit.span())),
+        syn::Lit::Str(lit) => Ok(Key::Str(lit.value(), lit.span())),
+        other => {
+            let msg = "unsupported by #[remain::sorted]";
+            Err(Error::new_spanned(other, msg))
+        }
+    }
 }
 
 fn idents_of_path(path: &syn::Path) -> Vec<Ident> {
@@ -178,3 +458,34 @@ fn is_just_ident(pat: &PatIdent) -> bool {
 }
 
 pub(crate) type Category = u8;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proc_macro2::Span;
+
+    #[test]
+    fn mixed_literal_kinds_are_rejected() {
+        let keys = [
+            Key::Int(1, Span::call_site()),
+            Key::Str("a".to_owned(), Span::call_site()),
+        ];
+
+        let err = ensure_consistent_kinds(keys.iter()).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("cannot mix different kinds of literal patterns"));
+    }
+
+    #[test]
+    fn or_pattern_rejects_misordered_literal_and_range() {
+        let arm: Arm = syn::parse_str("'z'..'~' | 'a' => {}").unwrap();
+        let pat = match arm.pat {
+            Pat::Or(pat) => pat,
+            _ => panic!("expected an or-pattern"),
+        };
+
+        let err = check_or_pattern(&pat, OrderMode::Lexical).unwrap_err();
+        assert!(err.to_string().contains("should sort before"));
+    }
+}
@@ -0,0 +1,313 @@
+use proc_macro2::{Ident, Span};
+use std::cmp::Ordering;
+
+use crate::check::Category;
+
+/// The value an item is ordered by, plus enough information to point back
+/// at the original tokens when a misordering needs to be reported.
+pub enum Key {
+    Ident(Vec<Ident>),
+    /// A `#[remain::key("...")]` override: sorts as though the item were
+    /// named this string, without touching the real identifier.
+    Custom(String, Span),
+    Int(i128, Span),
+    Char(char, Span),
+    Bytes(Vec<u8>, Span),
+    Str(String, Span),
+    Range {
+        start: Box<Key>,
+        end: Box<Key>,
+        inclusive: bool,
+    },
+    Wild(Span),
+}
+
+impl Key {
+    pub fn span(&self) -> Span {
+        match self {
+            Key::Ident(segments) => segments
+                .last()
+                .map(Ident::span)
+                .unwrap_or_else(Span::call_site),
+            Key::Custom(_, span)
+            | Key::Int(_, span)
+            | Key::Char(_, span)
+            | Key::Bytes(_, span)
+            | Key::Str(_, span)
+            | Key::Wild(span) => *span,
+            Key::Range { start, .. } => start.span(),
+        }
+    }
+
+    /// The path segments this key sorts by, for keys that are
+    /// identifier-like (a real path, or a `#[remain::key("...")]` override).
+    fn name(&self) -> Option<Vec<String>> {
+        match self {
+            Key::Ident(segments) => Some(segments.iter().map(Ident::to_string).collect()),
+            Key::Custom(value, _) => Some(vec![value.clone()]),
+            _ => None,
+        }
+    }
+}
+
+/// The family a key belongs to, used to reject matches that mix literal
+/// kinds (e.g. an int arm next to a string arm).
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum KeyKind {
+    Ident,
+    Int,
+    Char,
+    Bytes,
+    Str,
+}
+
+/// A wildcard arm has no kind of its own; it sorts greater than everything.
+pub fn kind_of(key: &Key) -> Option<KeyKind> {
+    match key {
+        Key::Ident(_) | Key::Custom(..) => Some(KeyKind::Ident),
+        Key::Int(..) => Some(KeyKind::Int),
+        Key::Char(..) => Some(KeyKind::Char),
+        Key::Bytes(..) => Some(KeyKind::Bytes),
+        Key::Str(..) => Some(KeyKind::Str),
+        Key::Range { start, .. } => kind_of(start),
+        Key::Wild(_) => None,
+    }
+}
+
+#[derive(Copy, Clone)]
+pub enum UnderscoreOrder {
+    First,
+    Last,
+}
+
+/// The ordering strategy selected via `#[remain::sorted(...)]`, applied to
+/// identifier segments (variant, field, arm and impl-item names).
+#[derive(Copy, Clone, Default)]
+pub enum OrderMode {
+    #[default]
+    Lexical,
+    Natural,
+    CaseInsensitive,
+}
+
+pub fn cmp(
+    this: &(Category, Key),
+    other: &(Category, Key),
+    underscore_order: UnderscoreOrder,
+    order_mode: OrderMode,
+) -> Ordering {
+    this.0
+        .cmp(&other.0)
+        .then_with(|| cmp_key(&this.1, &other.1, underscore_order, order_mode))
+}
+
+fn cmp_key(
+    this: &Key,
+    other: &Key,
+    underscore_order: UnderscoreOrder,
+    order_mode: OrderMode,
+) -> Ordering {
+    if let (Some(this_name), Some(other_name)) = (this.name(), other.name()) {
+        return cmp_names(&this_name, &other_name, underscore_order, order_mode);
+    }
+
+    match (this, other) {
+        (Key::Wild(_), Key::Wild(_)) => Ordering::Equal,
+        (Key::Wild(_), _) => Ordering::Greater,
+        (_, Key::Wild(_)) => Ordering::Less,
+        _ => {
+            // A bare literal is a degenerate range (its own start and end),
+            // so a literal compares against a range the same way two ranges
+            // compare against each other: by start bound, falling back to
+            // the end bound on ties.
+            let (this_start, this_end) = bounds(this);
+            let (other_start, other_end) = bounds(other);
+            cmp_literal(this_start, other_start).then_with(|| cmp_literal(this_end, other_end))
+        }
+    }
+}
+
+fn bounds(key: &Key) -> (&Key, &Key) {
+    match key {
+        Key::Range { start, end, .. } => (start, end),
+        other => (other, other),
+    }
+}
+
+/// Mixed literal *kinds* (e.g. an int next to a string) are rejected by
+/// `check::ensure_consistent_kinds` before `cmp` is ever called on them, so
+/// this only needs to compare literals of the same kind.
+fn cmp_literal(this: &Key, other: &Key) -> Ordering {
+    match (this, other) {
+        (Key::Int(this, _), Key::Int(other, _)) => this.cmp(other),
+        (Key::Char(this, _), Key::Char(other, _)) => this.cmp(other),
+        (Key::Bytes(this, _), Key::Bytes(other, _)) => this.cmp(other),
+        (Key::Str(this, _), Key::Str(other, _)) => this.cmp(other),
+        _ => Ordering::Equal,
+    }
+}
+
+fn cmp_names(
+    this: &[String],
+    other: &[String],
+    underscore_order: UnderscoreOrder,
+    order_mode: OrderMode,
+) -> Ordering {
+    let mut this = this.iter().peekable();
+    let mut other = other.iter().peekable();
+
+    loop {
+        match (this.next(), other.next()) {
+            (Some(this), Some(other)) => {
+                match cmp_segment(this, other, underscore_order, order_mode) {
+                    Ordering::Equal => {}
+                    order => return order,
+                }
+            }
+            (Some(_), None) => return Ordering::Greater,
+            (None, Some(_)) => return Ordering::Less,
+            (None, None) => return Ordering::Equal,
+        }
+    }
+}
+
+fn cmp_segment(
+    this: &str,
+    other: &str,
+    underscore_order: UnderscoreOrder,
+    order_mode: OrderMode,
+) -> Ordering {
+    let this_underscore = this.starts_with('_');
+    let other_underscore = other.starts_with('_');
+
+    if this_underscore != other_underscore {
+        return match underscore_order {
+            UnderscoreOrder::First if this_underscore => Ordering::Less,
+            UnderscoreOrder::First => Ordering::Greater,
+            UnderscoreOrder::Last if this_underscore => Ordering::Greater,
+            UnderscoreOrder::Last => Ordering::Less,
+        };
+    }
+
+    match order_mode {
+        OrderMode::Lexical => this.cmp(other),
+        OrderMode::Natural => cmp_natural(this, other),
+        OrderMode::CaseInsensitive => this
+            .to_lowercase()
+            .cmp(&other.to_lowercase())
+            .then_with(|| this.cmp(other)),
+    }
+}
+
+/// Digit-aware comparison: splits each segment into maximal runs of ASCII
+/// digits vs. non-digits and compares run by run, so `col2 < col10` instead
+/// of the lexical `col10 < col2`.
+fn cmp_natural(this: &str, other: &str) -> Ordering {
+    let mut this_runs = digit_runs(this).peekable();
+    let mut other_runs = digit_runs(other).peekable();
+
+    loop {
+        match (this_runs.next(), other_runs.next()) {
+            (Some(this), Some(other)) => {
+                let order = match (is_digit_run(this), is_digit_run(other)) {
+                    (true, true) => cmp_numeric_run(this, other),
+                    (false, false) => this.cmp(other),
+                    (true, false) => Ordering::Less,
+                    (false, true) => Ordering::Greater,
+                };
+                if order != Ordering::Equal {
+                    return order;
+                }
+            }
+            (Some(_), None) => return Ordering::Greater,
+            (None, Some(_)) => return Ordering::Less,
+            (None, None) => return Ordering::Equal,
+        }
+    }
+}
+
+fn digit_runs(s: &str) -> impl Iterator<Item = &str> {
+    let bytes = s.as_bytes();
+    let mut start = 0;
+    (1..=bytes.len()).filter_map(move |i| {
+        if i == bytes.len() || is_digit(bytes[i]) != is_digit(bytes[i - 1]) {
+            let run = &s[start..i];
+            start = i;
+            Some(run)
+        } else {
+            None
+        }
+    })
+}
+
+fn is_digit_run(run: &str) -> bool {
+    run.as_bytes().first().copied().is_some_and(is_digit)
+}
+
+fn is_digit(byte: u8) -> bool {
+    byte.is_ascii_digit()
+}
+
+fn cmp_numeric_run(this: &str, other: &str) -> Ordering {
+    let this = this.trim_start_matches('0');
+    let other = other.trim_start_matches('0');
+
+    this.len().cmp(&other.len()).then_with(|| this.cmp(other))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(value: i128) -> (Category, Key) {
+        (0, Key::Int(value, Span::call_site()))
+    }
+
+    fn int_range(start: i128, end: i128) -> (Category, Key) {
+        (
+            0,
+            Key::Range {
+                start: Box::new(Key::Int(start, Span::call_site())),
+                end: Box::new(Key::Int(end, Span::call_site())),
+                inclusive: true,
+            },
+        )
+    }
+
+    fn char_range(start: char, end: char) -> (Category, Key) {
+        (
+            0,
+            Key::Range {
+                start: Box::new(Key::Char(start, Span::call_site())),
+                end: Box::new(Key::Char(end, Span::call_site())),
+                inclusive: false,
+            },
+        )
+    }
+
+    #[test]
+    fn literal_sorts_before_later_range_of_same_kind() {
+        assert_eq!(
+            cmp(
+                &int(1),
+                &int_range(2, 4),
+                UnderscoreOrder::First,
+                OrderMode::Lexical
+            ),
+            Ordering::Less,
+        );
+    }
+
+    #[test]
+    fn range_sorts_after_lesser_literal_of_same_kind() {
+        assert_eq!(
+            cmp(
+                &char_range('a', 'z'),
+                &(0, Key::Char('A', Span::call_site())),
+                UnderscoreOrder::First,
+                OrderMode::Lexical,
+            ),
+            Ordering::Greater,
+        );
+    }
+}
@@ -0,0 +1,39 @@
+use proc_macro2::Ident;
+use syn::Error;
+
+use crate::compare::Key;
+
+pub fn error(lesser: &Key, greater: &Key) -> Error {
+    let msg = format!(
+        "{} should sort before {}",
+        describe(lesser),
+        describe(greater),
+    );
+    Error::new(greater.span(), msg)
+}
+
+fn describe(key: &Key) -> String {
+    match key {
+        Key::Ident(segments) => segments
+            .iter()
+            .map(Ident::to_string)
+            .collect::<Vec<_>>()
+            .join("::"),
+        Key::Custom(value, _) => value.clone(),
+        Key::Int(value, _) => value.to_string(),
+        Key::Char(value, _) => format!("{:?}", value),
+        Key::Bytes(value, _) => format!("{:?}", value),
+        Key::Str(value, _) => format!("{:?}", value),
+        Key::Range {
+            start,
+            end,
+            inclusive,
+        } => format!(
+            "{}..{}{}",
+            describe(start),
+            if *inclusive { "=" } else { "" },
+            describe(end),
+        ),
+        Key::Wild(_) => "_".to_owned(),
+    }
+}
@@ -0,0 +1,44 @@
+extern crate proc_macro;
+
+mod check;
+mod compare;
+mod format;
+mod parse;
+
+use proc_macro::TokenStream;
+use quote::ToTokens;
+use syn::parse_macro_input;
+
+use crate::parse::{Input, SortedArgs};
+
+#[proc_macro_attribute]
+pub fn sorted(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as SortedArgs);
+    let mut input = parse_macro_input!(input as Input);
+    let result = check::sorted(&mut input, args.mode, args.fix);
+    finish(input, result)
+}
+
+#[proc_macro_attribute]
+pub fn check(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as SortedArgs);
+    let mut item_fn = parse_macro_input!(input as syn::ItemFn);
+    let result = check::check_fn(&mut item_fn, args);
+    finish(item_fn, result)
+}
+
+#[proc_macro_attribute]
+pub fn unsorted(_args: TokenStream, input: TokenStream) -> TokenStream {
+    input
+}
+
+fn finish<T: ToTokens>(tokens: T, result: syn::Result<()>) -> TokenStream {
+    let tokens = tokens.into_token_stream();
+    match result {
+        Ok(()) => tokens.into(),
+        Err(err) => {
+            let compile_error = err.to_compile_error();
+            quote::quote!(#compile_error #tokens).into()
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use syn::parse::discouraged::Speculative;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{
+    Error, Expr, ExprMatch, Ident, Item, ItemEnum, ItemImpl, ItemStruct, Result, Stmt, Token,
+};
+
+use crate::compare::OrderMode;
+
+/// The arguments accepted by `#[remain::sorted(...)]` (and the matching
+/// `#[remain::check(...)]`), e.g. `natural`, `case_insensitive` or `fix`.
+#[derive(Default, Clone, Copy)]
+pub struct SortedArgs {
+    pub mode: OrderMode,
+    pub fix: bool,
+}
+
+impl Parse for SortedArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut mode = OrderMode::Lexical;
+        let mut fix = false;
+
+        for ident in Punctuated::<Ident, Token![,]>::parse_terminated(input)? {
+            match ident.to_string().as_str() {
+                "natural" => mode = OrderMode::Natural,
+                "case_insensitive" => mode = OrderMode::CaseInsensitive,
+                "fix" => fix = true,
+                _ => {
+                    let msg = "unrecognized #[remain::sorted] argument";
+                    return Err(Error::new_spanned(ident, msg));
+                }
+            }
+        }
+
+        Ok(SortedArgs { mode, fix })
+    }
+}
+
+pub enum Input {
+    Enum(ItemEnum),
+    Impl(ItemImpl),
+    Struct(ItemStruct),
+    Match(ExprMatch),
+    Let(ExprMatch),
+}
+
+impl ToTokens for Input {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            Input::Enum(item) => item.to_tokens(tokens),
+            Input::Impl(item) => item.to_tokens(tokens),
+            Input::Struct(item) => item.to_tokens(tokens),
+            Input::Match(expr) => expr.to_tokens(tokens),
+            Input::Let(expr) => expr.to_tokens(tokens),
+        }
+    }
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let ahead = input.fork();
+        if let Ok(item) = ahead.parse::<Item>() {
+            let found = match item {
+                Item::Enum(item) => Some(Input::Enum(item)),
+                Item::Impl(item) => Some(Input::Impl(item)),
+                Item::Struct(item) => Some(Input::Struct(item)),
+                _ => None,
+            };
+            if let Some(input_found) = found {
+                input.advance_to(&ahead);
+                return Ok(input_found);
+            }
+        }
+
+        match input.parse::<Stmt>()? {
+            Stmt::Expr(Expr::Match(expr)) | Stmt::Semi(Expr::Match(expr), _) => {
+                Ok(Input::Match(expr))
+            }
+            Stmt::Local(local) => match local.init {
+                Some((_, init)) => match *init {
+                    Expr::Match(expr) => Ok(Input::Let(expr)),
+                    other => Err(Error::new_spanned(
+                        other,
+                        "unsupported by #[remain::sorted]",
+                    )),
+                },
+                None => Err(Error::new_spanned(
+                    local,
+                    "unsupported by #[remain::sorted]",
+                )),
+            },
+            other => Err(Error::new_spanned(
+                other,
+                "unsupported by #[remain::sorted]",
+            )),
+        }
+    }
+}
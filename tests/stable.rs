@@ -26,6 +26,42 @@ pub struct TestStruct {
     d: usize,
 }
 
+#[remain::sorted(natural)]
+#[derive(PartialEq)]
+pub enum TestEnumNatural {
+    Col1,
+    Col2,
+    Col10,
+}
+
+#[remain::sorted(case_insensitive)]
+#[derive(PartialEq)]
+pub enum TestEnumCaseInsensitive {
+    A,
+    #[allow(non_camel_case_types)]
+    a,
+    B,
+}
+
+#[remain::sorted]
+#[derive(PartialEq)]
+pub struct TestStructKeyOverride {
+    age: usize,
+    name: usize,
+    #[remain::key("social_security_number")]
+    ssn: usize,
+}
+
+#[remain::sorted]
+#[derive(PartialEq)]
+pub struct TestStructUnsortedKeyOverride {
+    a: usize,
+    #[remain::unsorted]
+    #[remain::key("not_checked")]
+    z: usize,
+    b: usize,
+}
+
 #[test]
 fn test_attrs() {
     fn is_partial_eq<T: PartialEq>() -> bool {
@@ -34,6 +70,10 @@ fn test_attrs() {
 
     assert!(is_partial_eq::<TestEnum>());
     assert!(is_partial_eq::<TestStruct>());
+    assert!(is_partial_eq::<TestEnumNatural>());
+    assert!(is_partial_eq::<TestEnumCaseInsensitive>());
+    assert!(is_partial_eq::<TestStructKeyOverride>());
+    assert!(is_partial_eq::<TestStructUnsortedKeyOverride>());
 }
 
 #[test]
@@ -71,3 +111,146 @@ fn test_match() {
         _ => {}
     }
 }
+
+#[test]
+#[remain::check]
+fn test_match_int() {
+    let value = 5;
+
+    #[sorted]
+    match value {
+        1 => {}
+        2..=4 => {}
+        5 => {}
+        6..=9 => {}
+        _ => {}
+    }
+}
+
+#[test]
+#[remain::check]
+fn test_match_char() {
+    let value = 'b';
+
+    #[sorted]
+    match value {
+        'a' => {}
+        'b'..'z' => {}
+        _ => {}
+    }
+}
+
+#[test]
+#[remain::check]
+fn test_match_int_and_range_sorted() {
+    let value = 5;
+
+    #[sorted]
+    match value {
+        1 => {}
+        2..=4 => {}
+        5..=9 => {}
+        10 => {}
+        _ => {}
+    }
+}
+
+#[test]
+#[remain::check]
+fn test_match_char_and_range_sorted() {
+    let value = 'b';
+
+    #[sorted]
+    match value {
+        'a' => {}
+        'b'..'m' => {}
+        'm'..'z' => {}
+        _ => {}
+    }
+}
+
+#[test]
+#[remain::check]
+fn test_match_or_pattern() {
+    let value = 'b';
+
+    #[sorted]
+    match value {
+        'a' | 'b' => {}
+        'c' | 'e' | 'g' => {}
+        'x'..'z' => {}
+        _ => {}
+    }
+}
+
+#[test]
+#[remain::check]
+fn test_match_or_pattern_with_range() {
+    let value = 'a';
+
+    #[sorted]
+    match value {
+        'a' | 'b'..'m' => {}
+        'o'..'z' => {}
+        _ => {}
+    }
+}
+
+#[test]
+#[remain::check]
+fn test_match_unsorted_or_pattern() {
+    let value = 'b';
+
+    #[sorted]
+    match value {
+        'a' => {}
+        #[remain::unsorted]
+        'c' | 'b' => {}
+        _ => {}
+    }
+}
+
+#[remain::sorted(fix)]
+#[derive(PartialEq)]
+pub enum TestEnumFix {
+    C,
+    A,
+    #[remain::unsorted]
+    Pinned,
+    B,
+}
+
+#[test]
+fn test_fix() {
+    assert_eq!(TestEnumFix::A as u8, 0);
+    assert_eq!(TestEnumFix::B as u8, 1);
+    assert_eq!(TestEnumFix::Pinned as u8, 2);
+    assert_eq!(TestEnumFix::C as u8, 3);
+}
+
+#[test]
+#[remain::check(fix)]
+fn test_check_fix() {
+    let value = TestEnum::B;
+
+    #[sorted]
+    match value {
+        TestEnum::B => {}
+        TestEnum::A => {}
+        TestEnum::C => {}
+        _ => {}
+    }
+}
+
+#[test]
+#[remain::check]
+fn test_match_str() {
+    let value = "bar";
+
+    #[sorted]
+    match value {
+        "bar" => {}
+        "foo" => {}
+        _ => {}
+    }
+}